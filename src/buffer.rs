@@ -0,0 +1,240 @@
+use crate::client::{Client, Error};
+use crate::metrics::{Serie, Type};
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+type MergeKey = (String, Option<String>, Vec<String>, Type, Option<i64>);
+
+enum Command {
+    Submit(Serie),
+    Flush(oneshot::Sender<Result<(), Error>>),
+    Shutdown(oneshot::Sender<Result<(), Error>>),
+}
+
+pub struct BufferConfig {
+    flush_interval: Duration,
+    max_batch_size: usize,
+    channel_capacity: usize,
+}
+
+impl BufferConfig {
+    pub fn new() -> Self {
+        Self {
+            flush_interval: Duration::from_secs(10),
+            max_batch_size: 1000,
+            channel_capacity: 10_000,
+        }
+    }
+
+    /// How often the background task flushes the buffer, absent a
+    /// `max_batch_size` trigger. Defaults to 10s.
+    pub fn set_flush_interval(mut self, value: Duration) -> Self {
+        self.flush_interval = value;
+        self
+    }
+
+    /// Flushes as soon as the buffer holds this many points. Defaults to 1000.
+    pub fn set_max_batch_size(mut self, value: usize) -> Self {
+        self.max_batch_size = value;
+        self
+    }
+
+    /// Size of the bounded submission queue. Once full, `submit` drops the
+    /// point rather than growing memory unbounded. Defaults to 10000.
+    pub fn set_channel_capacity(mut self, value: usize) -> Self {
+        self.channel_capacity = value;
+        self
+    }
+}
+
+impl Default for BufferConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Returned by [`BufferedClient::submit`] when the submission queue is
+/// saturated; the point is dropped rather than buffered.
+#[derive(Debug)]
+pub enum SubmitError {
+    QueueFull,
+}
+
+/// Wraps a [`Client`] and coalesces individually submitted `Serie`s into
+/// batched `post_metrics` calls made from a background Tokio task. Points
+/// are submitted inside a `Serie`; there is no point-level submit method.
+pub struct BufferedClient {
+    sender: mpsc::Sender<Command>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl BufferedClient {
+    pub fn new(client: Client, config: BufferConfig) -> Self {
+        let (sender, receiver) = mpsc::channel(config.channel_capacity);
+        let handle = tokio::spawn(run(
+            client,
+            config.flush_interval,
+            config.max_batch_size,
+            receiver,
+        ));
+        Self {
+            sender,
+            handle: Some(handle),
+        }
+    }
+
+    /// Enqueues `serie` for the next flush, merging it with any buffered
+    /// serie sharing the same metric, host, tags, type and interval.
+    pub fn submit(&self, serie: Serie) -> Result<(), SubmitError> {
+        self.sender
+            .try_send(Command::Submit(serie))
+            .map_err(|_| SubmitError::QueueFull)
+    }
+
+    /// Flushes the buffer immediately and waits for the submission to complete.
+    pub async fn flush(&self) -> Result<(), Error> {
+        let (reply, response) = oneshot::channel();
+        if self.sender.send(Command::Flush(reply)).await.is_err() {
+            return Ok(());
+        }
+        response.await.unwrap_or(Ok(()))
+    }
+
+    /// Flushes any buffered points and stops the background task.
+    pub async fn shutdown(mut self) -> Result<(), Error> {
+        let (reply, response) = oneshot::channel();
+        let result = if self.sender.send(Command::Shutdown(reply)).await.is_ok() {
+            response.await.unwrap_or(Ok(()))
+        } else {
+            Ok(())
+        };
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.await;
+        }
+        result
+    }
+}
+
+async fn run(
+    client: Client,
+    flush_interval: Duration,
+    max_batch_size: usize,
+    mut receiver: mpsc::Receiver<Command>,
+) {
+    let mut buffer: HashMap<MergeKey, Serie> = HashMap::new();
+    let mut ticker = tokio::time::interval(flush_interval);
+    loop {
+        tokio::select! {
+            _ = ticker.tick() => {
+                let _ = flush_buffer(&client, &mut buffer).await;
+            }
+            command = receiver.recv() => {
+                match command {
+                    Some(Command::Submit(serie)) => {
+                        insert(&mut buffer, serie);
+                        if point_count(&buffer) >= max_batch_size {
+                            let _ = flush_buffer(&client, &mut buffer).await;
+                        }
+                    }
+                    Some(Command::Flush(reply)) => {
+                        let _ = reply.send(flush_buffer(&client, &mut buffer).await);
+                    }
+                    Some(Command::Shutdown(reply)) => {
+                        let _ = reply.send(flush_buffer(&client, &mut buffer).await);
+                        break;
+                    }
+                    None => break,
+                }
+            }
+        }
+    }
+}
+
+fn insert(buffer: &mut HashMap<MergeKey, Serie>, serie: Serie) {
+    match buffer.entry(serie.merge_key()) {
+        Entry::Occupied(mut existing) => existing.get_mut().merge(serie),
+        Entry::Vacant(empty) => {
+            empty.insert(serie);
+        }
+    }
+}
+
+fn point_count(buffer: &HashMap<MergeKey, Serie>) -> usize {
+    buffer.values().map(Serie::point_count).sum()
+}
+
+async fn flush_buffer(client: &Client, buffer: &mut HashMap<MergeKey, Serie>) -> Result<(), Error> {
+    if buffer.is_empty() {
+        return Ok(());
+    }
+    let series: Vec<Serie> = buffer.drain().map(|(_, serie)| serie).collect();
+    client.post_metrics(&series).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::Config;
+    use crate::metrics::Point;
+    use mockito::mock;
+
+    #[test]
+    fn insert_merges_points_for_identical_metric_host_and_tags() {
+        let mut buffer = HashMap::new();
+        insert(
+            &mut buffer,
+            Serie::new("cpu.usage", Type::Gauge)
+                .set_host("web-1")
+                .add_point(Point::new(1, 1.0)),
+        );
+        insert(
+            &mut buffer,
+            Serie::new("cpu.usage", Type::Gauge)
+                .set_host("web-1")
+                .add_point(Point::new(2, 2.0)),
+        );
+        assert_eq!(buffer.len(), 1);
+        assert_eq!(point_count(&buffer), 2);
+    }
+
+    #[test]
+    fn insert_keeps_series_with_different_type_or_interval_distinct() {
+        let mut buffer = HashMap::new();
+        insert(
+            &mut buffer,
+            Serie::new("cpu.usage", Type::Gauge)
+                .set_host("web-1")
+                .add_point(Point::new(1, 1.0)),
+        );
+        insert(
+            &mut buffer,
+            Serie::new("cpu.usage", Type::Count)
+                .set_host("web-1")
+                .set_interval(10)
+                .add_point(Point::new(2, 2.0)),
+        );
+        assert_eq!(buffer.len(), 2);
+        assert_eq!(point_count(&buffer), 2);
+    }
+
+    #[tokio::test]
+    async fn flush_sends_the_buffered_series() {
+        let call = mock("POST", "/api/v1/series").with_status(202).create();
+        let client = Client::new(Config::new(
+            mockito::server_url(),
+            String::from("fake-api-key"),
+        ))
+        .unwrap();
+        let buffered = BufferedClient::new(client, BufferConfig::new());
+        buffered
+            .submit(Serie::new("cpu.usage", Type::Gauge).add_point(Point::new(1, 1.0)))
+            .unwrap();
+        let result = buffered.flush().await;
+        assert!(result.is_ok());
+        call.expect(1);
+        buffered.shutdown().await.unwrap();
+    }
+}
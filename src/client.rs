@@ -1,10 +1,21 @@
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rand::Rng;
 use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
+use std::io::Write;
+use std::time::Duration;
 
 #[derive(Debug)]
 pub enum Error {
     Reqwest(reqwest::Error),
     Body(StatusCode, Vec<String>),
+    RetriesExhausted(Box<Error>),
+    Serialization(serde_json::Error),
+    Io(std::io::Error),
+    Validation(Vec<String>),
+    InvalidConfig(String),
 }
 
 impl From<reqwest::Error> for Error {
@@ -13,49 +24,279 @@ impl From<reqwest::Error> for Error {
     }
 }
 
+impl From<serde_json::Error> for Error {
+    fn from(err: serde_json::Error) -> Self {
+        Self::Serialization(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
 #[derive(Deserialize)]
 struct BodyError {
     errors: Vec<String>,
 }
 
+/// Extracts the `errors` array from a Datadog-style JSON error body. Proxies
+/// and load balancers in front of Datadog commonly return plain text or HTML
+/// for 502/503 responses instead of this shape, so a body that doesn't
+/// parse is reported as a single raw-text error rather than failing the
+/// whole request and bypassing the retry logic.
+fn parse_body_errors(body: String) -> Vec<String> {
+    match serde_json::from_str::<BodyError>(&body) {
+        Ok(parsed) => parsed.errors,
+        Err(_) if body.is_empty() => Vec::new(),
+        Err(_) => vec![body],
+    }
+}
+
 pub struct Config {
     host: String,
     api_key: String,
+    max_retries: u32,
+    initial_backoff: Duration,
+    max_backoff: Duration,
+    gzip_threshold: usize,
+    validate_payloads: bool,
+    request_timeout: Duration,
+    pool_max_idle_per_host: usize,
 }
 
 impl Config {
     pub fn new(host: String, api_key: String) -> Self {
-        Self { host, api_key }
+        Self {
+            host,
+            api_key,
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+            gzip_threshold: 1024,
+            validate_payloads: false,
+            request_timeout: Duration::from_secs(30),
+            pool_max_idle_per_host: 8,
+        }
+    }
+
+    /// Maximum number of retry attempts for requests that fail with a
+    /// retryable error (connection/timeout, 429 or 5xx). Defaults to 3.
+    pub fn set_max_retries(mut self, value: u32) -> Self {
+        self.max_retries = value;
+        self
+    }
+
+    /// Base delay used by the exponential backoff. Defaults to 100ms.
+    pub fn set_initial_backoff(mut self, value: Duration) -> Self {
+        self.initial_backoff = value;
+        self
+    }
+
+    /// Upper bound for the backoff delay, regardless of the attempt number.
+    /// Defaults to 10s.
+    pub fn set_max_backoff(mut self, value: Duration) -> Self {
+        self.max_backoff = value;
+        self
+    }
+
+    /// Serialized payloads larger than this many bytes are gzip-compressed
+    /// before being sent, with `Content-Encoding: gzip` set accordingly.
+    /// Defaults to 1024 bytes; pass `usize::MAX` to disable compression.
+    pub fn set_gzip_threshold(mut self, value: usize) -> Self {
+        self.gzip_threshold = value;
+        self
+    }
+
+    /// When enabled, `post_event` and `post_metrics` validate their payload
+    /// against Datadog's documented constraints and return
+    /// `Error::Validation` instead of making the HTTP call. Defaults to
+    /// disabled.
+    pub fn set_validate_payloads(mut self, value: bool) -> Self {
+        self.validate_payloads = value;
+        self
+    }
+
+    /// Timeout applied to the request/response cycle of each attempt; every
+    /// retry gets the full timeout again. Defaults to 30s.
+    pub fn set_request_timeout(mut self, value: Duration) -> Self {
+        self.request_timeout = value;
+        self
+    }
+
+    /// Maximum number of idle pooled connections kept open per host.
+    /// Defaults to 8.
+    pub fn set_pool_max_idle_per_host(mut self, value: usize) -> Self {
+        self.pool_max_idle_per_host = value;
+        self
     }
 }
 
 pub struct Client {
     config: Config,
+    http: reqwest::Client,
 }
 
 impl Client {
-    pub fn new(config: Config) -> Self {
-        Self { config }
+    /// Builds a `Client` from `config`. Fails with `Error::InvalidConfig` if
+    /// `api_key` contains bytes that cannot be carried in an HTTP header
+    /// value, or if the underlying `reqwest::Client` cannot be built.
+    pub fn new(config: Config) -> Result<Self, Error> {
+        let mut headers = reqwest::header::HeaderMap::new();
+        let api_key = reqwest::header::HeaderValue::from_str(&config.api_key)
+            .map_err(|err| Error::InvalidConfig(format!("invalid api_key: {}", err)))?;
+        headers.insert("DD-API-KEY", api_key);
+        let http = reqwest::Client::builder()
+            .default_headers(headers)
+            .timeout(config.request_timeout)
+            .pool_max_idle_per_host(config.pool_max_idle_per_host)
+            .build()
+            .map_err(|err| Error::InvalidConfig(format!("failed to build http client: {}", err)))?;
+        Ok(Self { config, http })
+    }
+
+    pub(crate) fn validate_payloads(&self) -> bool {
+        self.config.validate_payloads
     }
 }
 
+/// Current time as a POSIX timestamp in seconds, used to validate
+/// `date_happened` and point timestamps against Datadog's freshness window.
+pub(crate) fn now_unix() -> i64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .expect("system clock is before the Unix epoch")
+        .as_secs() as i64
+}
+
+fn is_retryable_status(status: StatusCode) -> bool {
+    status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error()
+}
+
+fn is_retryable_error(err: &reqwest::Error) -> bool {
+    err.is_timeout() || err.is_connect()
+}
+
+fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+/// Exponential backoff with full jitter: on attempt `n`, sleep a random
+/// duration in `[0, min(max_backoff, initial_backoff * 2^n))`.
+fn backoff(attempt: u32, initial_backoff: Duration, max_backoff: Duration) -> Duration {
+    let exp = initial_backoff
+        .as_millis()
+        .saturating_mul(1u128 << attempt.min(32));
+    let capped = exp.min(max_backoff.as_millis()).max(1) as u64;
+    let jitter = rand::thread_rng().gen_range(0..capped);
+    Duration::from_millis(jitter)
+}
+
+/// Gzip-compresses `body` when it exceeds `threshold`, returning the bytes
+/// to send together with the `Content-Encoding` header to use, if any.
+fn prepare_body(body: Vec<u8>, threshold: usize) -> Result<(Vec<u8>, Option<&'static str>), Error> {
+    if body.len() <= threshold {
+        return Ok((body, None));
+    }
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    encoder.write_all(&body)?;
+    Ok((encoder.finish()?, Some("gzip")))
+}
+
 impl Client {
     pub async fn post<T: Serialize>(&self, path: &str, payload: &T) -> Result<(), Error> {
-        let client = reqwest::Client::new();
         let url = format!("{}{}", self.config.host, path);
-        let response = client
-            .post(url.as_str())
-            .header("Content-Type", "application/json")
-            .header("DD-API-KEY", self.config.api_key.as_str())
-            .json(payload)
-            .send()
+        let (body, encoding) =
+            prepare_body(serde_json::to_vec(payload)?, self.config.gzip_threshold)?;
+        self.send_with_retry(|| {
+            let mut request = self
+                .http
+                .post(url.as_str())
+                .header("Content-Type", "application/json");
+            if let Some(encoding) = encoding {
+                request = request.header("Content-Encoding", encoding);
+            }
+            request.body(body.clone())
+        })
+        .await?;
+        Ok(())
+    }
+
+    pub async fn get<T: DeserializeOwned, Q: Serialize>(
+        &self,
+        path: &str,
+        query: &Q,
+    ) -> Result<T, Error> {
+        let url = format!("{}{}", self.config.host, path);
+        let response = self
+            .send_with_retry(|| self.http.get(url.as_str()).query(query))
             .await?;
-        let status = response.status();
-        if status.is_client_error() || status.is_server_error() {
-            let body = response.json::<BodyError>().await?;
-            Err(Error::Body(status, body.errors))
+        Ok(response.json::<T>().await?)
+    }
+
+    /// Sends the request built by `build` (one fresh `RequestBuilder` per
+    /// attempt), retrying on connection/timeout errors, HTTP 429 and 5xx with
+    /// exponential backoff and full jitter, honoring a `Retry-After` header
+    /// when present. Gives up after `max_retries`, wrapping the last error in
+    /// `Error::RetriesExhausted` once at least one retry was attempted.
+    async fn send_with_retry<F>(&self, mut build: F) -> Result<reqwest::Response, Error>
+    where
+        F: FnMut() -> reqwest::RequestBuilder,
+    {
+        let mut attempt = 0;
+        loop {
+            let result = build().send().await;
+
+            let delay = match result {
+                Ok(response) => {
+                    let status = response.status();
+                    if !(status.is_client_error() || status.is_server_error()) {
+                        return Ok(response);
+                    }
+                    let after = retry_after(response.headers());
+                    let errors = parse_body_errors(response.text().await?);
+                    if !is_retryable_status(status) || attempt >= self.config.max_retries {
+                        let err = Error::Body(status, errors);
+                        return Err(Self::finalize(err, attempt));
+                    }
+                    after.unwrap_or_else(|| {
+                        backoff(
+                            attempt,
+                            self.config.initial_backoff,
+                            self.config.max_backoff,
+                        )
+                    })
+                }
+                Err(err) => {
+                    if !is_retryable_error(&err) || attempt >= self.config.max_retries {
+                        return Err(Self::finalize(Error::from(err), attempt));
+                    }
+                    backoff(
+                        attempt,
+                        self.config.initial_backoff,
+                        self.config.max_backoff,
+                    )
+                }
+            };
+
+            tokio::time::sleep(delay).await;
+            attempt += 1;
+        }
+    }
+
+    /// Wraps `err` in `Error::RetriesExhausted` once at least one retry was
+    /// attempted, so callers can distinguish a first-try failure from one
+    /// that survived the retry budget.
+    fn finalize(err: Error, attempt: u32) -> Error {
+        if attempt > 0 {
+            Error::RetriesExhausted(Box::new(err))
         } else {
-            Ok(())
+            err
         }
     }
 }
@@ -64,7 +305,9 @@ impl Client {
 mod tests {
     use super::*;
     use crate::client::Config;
+    use flate2::read::GzDecoder;
     use mockito::mock;
+    use std::io::Read;
 
     #[tokio::test]
     async fn post_success() {
@@ -72,7 +315,8 @@ mod tests {
         let client = Client::new(Config::new(
             mockito::server_url(),
             String::from("fake-api-key"),
-        ));
+        ))
+        .unwrap();
         let result = client
             .post("/somewhere", &String::from("Hello World!"))
             .await;
@@ -89,11 +333,194 @@ mod tests {
         let client = Client::new(Config::new(
             mockito::server_url(),
             String::from("fake-api-key"),
-        ));
+        ))
+        .unwrap();
+        let result = client
+            .post("/somewhere", &String::from("Hello World!"))
+            .await;
+        assert!(matches!(result, Err(Error::Body(StatusCode::FORBIDDEN, _))));
+        call.expect(1);
+    }
+
+    #[tokio::test]
+    async fn post_gives_up_after_a_single_retry_with_an_unparseable_error_body() {
+        let failure = mock("POST", "/somewhere")
+            .with_status(503)
+            .with_body("<html>Service Unavailable</html>")
+            .expect(2)
+            .create();
+        let client = Client::new(
+            Config::new(mockito::server_url(), String::from("fake-api-key"))
+                .set_max_retries(1)
+                .set_initial_backoff(Duration::from_millis(1))
+                .set_max_backoff(Duration::from_millis(5)),
+        )
+        .unwrap();
         let result = client
             .post("/somewhere", &String::from("Hello World!"))
             .await;
         assert!(result.is_err());
+        failure.expect(2);
+    }
+
+    #[tokio::test]
+    async fn post_retries_on_server_error_then_succeeds() {
+        let failure = mock("POST", "/somewhere")
+            .with_status(503)
+            .with_body("{\"errors\":[\"Service unavailable\"]}")
+            .expect(1)
+            .create();
+        let success = mock("POST", "/somewhere").with_status(202).create();
+        let client = Client::new(
+            Config::new(mockito::server_url(), String::from("fake-api-key"))
+                .set_max_retries(1)
+                .set_initial_backoff(Duration::from_millis(1))
+                .set_max_backoff(Duration::from_millis(5)),
+        )
+        .unwrap();
+        let result = client
+            .post("/somewhere", &String::from("Hello World!"))
+            .await;
+        assert!(result.is_ok());
+        failure.expect(1);
+        success.expect(1);
+    }
+
+    #[tokio::test]
+    async fn post_non_retryable_error_with_non_json_body_surfaces_raw_text() {
+        let call = mock("POST", "/somewhere")
+            .with_status(403)
+            .with_body("<html>Forbidden</html>")
+            .create();
+        let client = Client::new(Config::new(
+            mockito::server_url(),
+            String::from("fake-api-key"),
+        ))
+        .unwrap();
+        let result = client
+            .post("/somewhere", &String::from("Hello World!"))
+            .await;
+        match result {
+            Err(Error::Body(StatusCode::FORBIDDEN, errors)) => {
+                assert_eq!(errors, vec![String::from("<html>Forbidden</html>")]);
+            }
+            other => panic!("expected Error::Body, got {:?}", other),
+        }
+        call.expect(1);
+    }
+
+    #[tokio::test]
+    async fn post_retries_when_error_body_is_not_json() {
+        let failure = mock("POST", "/somewhere")
+            .with_status(503)
+            .with_body("<html>Service Unavailable</html>")
+            .expect(1)
+            .create();
+        let success = mock("POST", "/somewhere").with_status(202).create();
+        let client = Client::new(
+            Config::new(mockito::server_url(), String::from("fake-api-key"))
+                .set_max_retries(1)
+                .set_initial_backoff(Duration::from_millis(1))
+                .set_max_backoff(Duration::from_millis(5)),
+        )
+        .unwrap();
+        let result = client
+            .post("/somewhere", &String::from("Hello World!"))
+            .await;
+        assert!(result.is_ok());
+        failure.expect(1);
+        success.expect(1);
+    }
+
+    #[tokio::test]
+    async fn post_gives_up_after_max_retries() {
+        let failure = mock("POST", "/somewhere")
+            .with_status(500)
+            .with_body("{\"errors\":[\"Internal error\"]}")
+            .expect(2)
+            .create();
+        let client = Client::new(
+            Config::new(mockito::server_url(), String::from("fake-api-key"))
+                .set_max_retries(1)
+                .set_initial_backoff(Duration::from_millis(1))
+                .set_max_backoff(Duration::from_millis(5)),
+        )
+        .unwrap();
+        let result = client
+            .post("/somewhere", &String::from("Hello World!"))
+            .await;
+        assert!(matches!(result, Err(Error::RetriesExhausted(_))));
+        failure.expect(2);
+    }
+
+    #[test]
+    fn prepare_body_leaves_small_payloads_uncompressed() {
+        let (body, encoding) = prepare_body(Vec::from(b"{}".as_slice()), 1024).unwrap();
+        assert_eq!(body, b"{}");
+        assert!(encoding.is_none());
+    }
+
+    #[test]
+    fn prepare_body_gzips_payloads_over_the_threshold_and_round_trips() {
+        let original = serde_json::to_vec(&String::from("x").repeat(64)).unwrap();
+        let (compressed, encoding) = prepare_body(original.clone(), 8).unwrap();
+        assert_eq!(encoding, Some("gzip"));
+        assert!(compressed.len() < original.len());
+        let mut decompressed = Vec::new();
+        GzDecoder::new(compressed.as_slice())
+            .read_to_end(&mut decompressed)
+            .unwrap();
+        assert_eq!(decompressed, original);
+    }
+
+    #[tokio::test]
+    async fn post_compresses_large_payloads() {
+        let call = mock("POST", "/somewhere")
+            .match_header("Content-Encoding", "gzip")
+            .with_status(202)
+            .create();
+        let client = Client::new(
+            Config::new(mockito::server_url(), String::from("fake-api-key")).set_gzip_threshold(8),
+        )
+        .unwrap();
+        let result = client
+            .post("/somewhere", &String::from("x").repeat(64))
+            .await;
+        assert!(result.is_ok());
+        call.expect(1);
+    }
+
+    #[tokio::test]
+    async fn post_reuses_the_configured_api_key_header() {
+        let call = mock("POST", "/somewhere")
+            .match_header("DD-API-KEY", "fake-api-key")
+            .with_status(202)
+            .create();
+        let client = Client::new(Config::new(
+            mockito::server_url(),
+            String::from("fake-api-key"),
+        ))
+        .unwrap();
+        let result = client
+            .post("/somewhere", &String::from("Hello World!"))
+            .await;
+        assert!(result.is_ok());
+        call.expect(1);
+    }
+
+    #[tokio::test]
+    async fn post_sends_plain_json_below_threshold() {
+        let call = mock("POST", "/somewhere")
+            .match_header("Content-Encoding", mockito::Matcher::Missing)
+            .with_status(202)
+            .create();
+        let client = Client::new(Config::new(
+            mockito::server_url(),
+            String::from("fake-api-key"),
+        ))
+        .unwrap();
+        let result = client.post("/somewhere", &String::from("small")).await;
+        assert!(result.is_ok());
         call.expect(1);
     }
 }
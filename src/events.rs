@@ -1,7 +1,8 @@
 use crate::client::{Client, Error};
-use serde::Serialize;
+use futures::stream::{self, Stream, StreamExt};
+use serde::{Deserialize, Serialize};
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum AlertType {
     Error,
@@ -13,7 +14,7 @@ pub enum AlertType {
     Snapshot,
 }
 
-#[derive(Debug, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Priority {
     Normal,
@@ -141,6 +142,48 @@ impl CreateEventPayload {
     }
 }
 
+const MAX_TITLE_LEN: usize = 100;
+const MAX_TEXT_LEN: usize = 4000;
+const MAX_AGGREGATION_KEY_LEN: usize = 100;
+const SEVEN_DAYS_SECONDS: i64 = 7 * 24 * 60 * 60;
+
+impl CreateEventPayload {
+    /// Checks `title`, `text` and `aggregation_key` against their documented
+    /// length limits and, if `date_happened` is set, that it is no more than
+    /// 7 days old. Collects every violation instead of stopping at the first
+    /// one.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        if self.title.chars().count() > MAX_TITLE_LEN {
+            errors.push(format!(
+                "title must be at most {} characters",
+                MAX_TITLE_LEN
+            ));
+        }
+        if self.text.chars().count() > MAX_TEXT_LEN {
+            errors.push(format!("text must be at most {} characters", MAX_TEXT_LEN));
+        }
+        if let Some(aggregation_key) = &self.aggregation_key {
+            if aggregation_key.chars().count() > MAX_AGGREGATION_KEY_LEN {
+                errors.push(format!(
+                    "aggregation_key must be at most {} characters",
+                    MAX_AGGREGATION_KEY_LEN
+                ));
+            }
+        }
+        if let Some(date_happened) = self.date_happened {
+            if date_happened < crate::client::now_unix() - SEVEN_DAYS_SECONDS {
+                errors.push(String::from("date_happened must not be older than 7 days"));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
 impl Client {
     /// Post an event
     ///
@@ -150,10 +193,156 @@ impl Client {
     /// https://docs.datadoghq.com/api/latest/events/#post-an-event
     ///
     pub async fn post_event(&self, event: &CreateEventPayload) -> Result<(), Error> {
+        if self.validate_payloads() {
+            event.validate().map_err(Error::Validation)?;
+        }
         self.post("/api/v1/events", event).await
     }
 }
 
+/// An event as returned by the events stream query endpoint.
+#[derive(Debug, Clone, Deserialize)]
+pub struct Event {
+    pub id: Option<String>,
+    pub title: String,
+    pub text: String,
+    pub date_happened: Option<i64>,
+    #[serde(default)]
+    pub alert_type: Option<AlertType>,
+    #[serde(default)]
+    pub priority: Option<Priority>,
+    #[serde(default)]
+    pub host: Option<String>,
+    #[serde(default)]
+    pub source_type_name: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+/// # Examples
+///
+/// ```
+/// use datadog_client::events::EventQuery;
+///
+/// let query = EventQuery::new(1609459200, 1609545600)
+///     .set_priority("normal".to_string())
+///     .add_tag("environment:prod".to_string())
+///     .add_source("chef".to_string());
+/// ```
+#[derive(Debug, Clone, Serialize)]
+pub struct EventQuery {
+    start: i64,
+    end: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    priority: Option<String>,
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "join_with_comma"
+    )]
+    tags: Vec<String>,
+    #[serde(
+        skip_serializing_if = "Vec::is_empty",
+        serialize_with = "join_with_comma"
+    )]
+    sources: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    after: Option<String>,
+}
+
+fn join_with_comma<S>(values: &[String], serializer: S) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_str(&values.join(","))
+}
+
+impl EventQuery {
+    pub fn new(start: i64, end: i64) -> Self {
+        Self {
+            start,
+            end,
+            priority: None,
+            tags: Vec::new(),
+            sources: Vec::new(),
+            after: None,
+        }
+    }
+
+    pub fn set_priority(mut self, value: String) -> Self {
+        self.priority = Some(value);
+        self
+    }
+
+    pub fn add_tag(mut self, value: String) -> Self {
+        self.tags.push(value);
+        self
+    }
+
+    pub fn add_source(mut self, value: String) -> Self {
+        self.sources.push(value);
+        self
+    }
+
+    fn set_cursor(mut self, cursor: String) -> Self {
+        self.after = Some(cursor);
+        self
+    }
+}
+
+/// A single page of queried events, together with the opaque cursor to
+/// fetch the next one, if any.
+#[derive(Debug, Clone)]
+pub struct EventsPage {
+    pub events: Vec<Event>,
+    pub cursor: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct EventsResponse {
+    #[serde(default)]
+    events: Vec<Event>,
+    #[serde(default)]
+    after: Option<String>,
+}
+
+impl Client {
+    /// Query the events stream for a single page of results.
+    ///
+    /// https://docs.datadoghq.com/api/latest/events/#query-the-event-stream
+    ///
+    pub async fn query_events(&self, query: &EventQuery) -> Result<EventsPage, Error> {
+        let response: EventsResponse = self.get("/api/v1/events", query).await?;
+        Ok(EventsPage {
+            events: response.events,
+            cursor: response.after,
+        })
+    }
+
+    /// Streams every event matching `query`, transparently following the
+    /// cursor until the server returns an empty one (either `null` or `""`).
+    pub fn stream_events(
+        &self,
+        query: EventQuery,
+    ) -> impl Stream<Item = Result<Event, Error>> + '_ {
+        stream::unfold(Some(query), move |state| async move {
+            let query = state?;
+            let (items, next): (Vec<Result<Event, Error>>, Option<EventQuery>) =
+                match self.query_events(&query).await {
+                    Ok(page) => {
+                        let next = page
+                            .cursor
+                            .filter(|cursor| !cursor.is_empty())
+                            .map(|cursor| query.set_cursor(cursor));
+                        (page.events.into_iter().map(Ok).collect(), next)
+                    }
+                    Err(err) => (vec![Err(err)], None),
+                };
+            Some((stream::iter(items), next))
+        })
+        .flatten()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -166,7 +355,8 @@ mod tests {
         let client = Client::new(Config::new(
             mockito::server_url(),
             String::from("fake-api-key"),
-        ));
+        ))
+        .unwrap();
         let event = CreateEventPayload::new(
             String::from("Some Event Title"),
             String::from("Some event text"),
@@ -186,7 +376,8 @@ mod tests {
         let client = Client::new(Config::new(
             mockito::server_url(),
             String::from("fake-api-key"),
-        ));
+        ))
+        .unwrap();
         let event = CreateEventPayload::new(
             String::from("Some Event Title"),
             String::from("Some event text"),
@@ -196,4 +387,105 @@ mod tests {
         assert!(result.is_err());
         call.expect(1);
     }
+
+    #[tokio::test]
+    async fn query_events_returns_a_single_page() {
+        let call = mock("GET", "/api/v1/events")
+            .match_query(mockito::Matcher::Exact("start=0&end=1".into()))
+            .with_status(200)
+            .with_body(
+                "{\"events\":[{\"id\":\"1\",\"title\":\"t\",\"text\":\"body\"}],\"after\":null}",
+            )
+            .create();
+        let client = Client::new(Config::new(
+            mockito::server_url(),
+            String::from("fake-api-key"),
+        ))
+        .unwrap();
+        let page = client.query_events(&EventQuery::new(0, 1)).await.unwrap();
+        assert_eq!(page.events.len(), 1);
+        assert_eq!(page.events[0].title, "t");
+        assert!(page.cursor.is_none());
+        call.expect(1);
+    }
+
+    #[tokio::test]
+    async fn stream_events_follows_the_cursor_until_exhausted() {
+        let first_page = mock("GET", "/api/v1/events")
+            .match_query(mockito::Matcher::Exact("start=0&end=1".into()))
+            .with_status(200)
+            .with_body("{\"events\":[{\"id\":\"1\",\"title\":\"first\",\"text\":\"body\"}],\"after\":\"cursor-2\"}")
+            .create();
+        let second_page = mock("GET", "/api/v1/events")
+            .match_query(mockito::Matcher::Exact("start=0&end=1&after=cursor-2".into()))
+            .with_status(200)
+            .with_body("{\"events\":[{\"id\":\"2\",\"title\":\"second\",\"text\":\"body\"}],\"after\":null}")
+            .create();
+        let client = Client::new(Config::new(
+            mockito::server_url(),
+            String::from("fake-api-key"),
+        ))
+        .unwrap();
+        let events: Vec<_> = client.stream_events(EventQuery::new(0, 1)).collect().await;
+        let titles: Vec<_> = events
+            .into_iter()
+            .map(|event| event.unwrap().title)
+            .collect();
+        assert_eq!(titles, vec!["first", "second"]);
+        first_page.expect(1);
+        second_page.expect(1);
+    }
+
+    #[tokio::test]
+    async fn stream_events_stops_on_an_empty_cursor() {
+        let page = mock("GET", "/api/v1/events")
+            .match_query(mockito::Matcher::Exact("start=0&end=1".into()))
+            .with_status(200)
+            .with_body(
+                "{\"events\":[{\"id\":\"1\",\"title\":\"only\",\"text\":\"body\"}],\"after\":\"\"}",
+            )
+            .create();
+        let client = Client::new(Config::new(
+            mockito::server_url(),
+            String::from("fake-api-key"),
+        ))
+        .unwrap();
+        let events: Vec<_> = client.stream_events(EventQuery::new(0, 1)).collect().await;
+        let titles: Vec<_> = events
+            .into_iter()
+            .map(|event| event.unwrap().title)
+            .collect();
+        assert_eq!(titles, vec!["only"]);
+        page.expect(1);
+    }
+
+    #[test]
+    fn validate_rejects_an_oversized_title_and_text() {
+        let event = CreateEventPayload::new(
+            String::from("t").repeat(101),
+            String::from("t").repeat(4001),
+        );
+        let errors = event.validate().unwrap_err();
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn validate_accepts_a_well_formed_payload() {
+        let event = CreateEventPayload::new(String::from("title"), String::from("text"));
+        assert!(event.validate().is_ok());
+    }
+
+    #[tokio::test]
+    async fn post_event_short_circuits_when_validation_fails() {
+        let call = mock("POST", "/api/v1/events").with_status(202).create();
+        let client = Client::new(
+            Config::new(mockito::server_url(), String::from("fake-api-key"))
+                .set_validate_payloads(true),
+        )
+        .unwrap();
+        let event = CreateEventPayload::new(String::from("t").repeat(101), String::from("text"));
+        let result = client.post_event(&event).await;
+        assert!(matches!(result, Err(Error::Validation(_))));
+        call.expect(0);
+    }
 }
@@ -0,0 +1,6 @@
+pub mod buffer;
+pub mod client;
+pub mod events;
+pub mod metrics;
+
+pub use client::{Client, Config, Error};
@@ -2,12 +2,13 @@ use crate::client::{Client, Error};
 use serde::ser::SerializeSeq;
 use serde::{Serialize, Serializer};
 
-#[derive(Clone, Debug, Serialize)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum Type {
     Count,
     Gauge,
     Rate,
+    Distribution,
 }
 
 #[derive(Clone, Debug)]
@@ -114,17 +115,194 @@ impl Serie {
     }
 }
 
+impl Serie {
+    /// Key identifying series that can be merged together: same metric name,
+    /// host, set of tags, type and interval. Two series that differ only in
+    /// `dtype` or `interval` must stay distinct, since merging them would
+    /// silently discard one side's value.
+    pub(crate) fn merge_key(&self) -> (String, Option<String>, Vec<String>, Type, Option<i64>) {
+        let mut tags = self.tags.clone();
+        tags.sort();
+        (
+            self.metric.clone(),
+            self.host.clone(),
+            tags,
+            self.dtype.clone(),
+            self.interval,
+        )
+    }
+
+    /// Appends `other`'s points to this serie. Assumes `other` shares this
+    /// serie's `merge_key`.
+    pub(crate) fn merge(&mut self, other: Serie) {
+        self.points.extend(other.points);
+    }
+
+    pub(crate) fn point_count(&self) -> usize {
+        self.points.len()
+    }
+}
+
+const POINT_MAX_FUTURE_SECONDS: i64 = 10 * 60;
+const POINT_MAX_PAST_SECONDS: i64 = 60 * 60;
+
+impl Serie {
+    /// Checks that `interval` is set when `dtype` is `Count` or `Rate`, and
+    /// that every point's timestamp falls within Datadog's accepted window
+    /// (no more than 10 minutes in the future or 1 hour in the past).
+    /// Collects every violation instead of stopping at the first one.
+    pub fn validate(&self) -> Result<(), Vec<String>> {
+        let mut errors = Vec::new();
+        if matches!(self.dtype, Type::Count | Type::Rate) && self.interval.is_none() {
+            errors.push(format!(
+                "interval must be set when type is {:?}",
+                self.dtype
+            ));
+        }
+        let now = crate::client::now_unix();
+        for point in &self.points {
+            let timestamp = point.timestamp as i64;
+            if timestamp > now + POINT_MAX_FUTURE_SECONDS {
+                errors.push(format!(
+                    "point timestamp {} is more than {} seconds in the future",
+                    timestamp, POINT_MAX_FUTURE_SECONDS
+                ));
+            } else if timestamp < now - POINT_MAX_PAST_SECONDS {
+                errors.push(format!(
+                    "point timestamp {} is more than {} seconds in the past",
+                    timestamp, POINT_MAX_PAST_SECONDS
+                ));
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+}
+
 impl Client {
     /// Submit metrics
     ///
     /// https://docs.datadoghq.com/api/latest/metrics/#submit-metrics
     ///
     pub async fn post_metrics(&self, series: &[Serie]) -> Result<(), Error> {
+        if self.validate_payloads() {
+            let mut errors = Vec::new();
+            for serie in series {
+                if let Err(violations) = serie.validate() {
+                    errors.extend(violations);
+                }
+            }
+            if !errors.is_empty() {
+                return Err(Error::Validation(errors));
+            }
+        }
         let payload = serde_json::json!({ "series": series });
         self.post("/api/v1/series", &payload).await
     }
 }
 
+/// A distribution point: a timestamp together with every sample collected
+/// for it. Serializes as `[timestamp, [v1, v2, ...]]`, unlike [`Point`]
+/// which carries a single scalar value.
+#[derive(Clone, Debug)]
+pub struct DistributionPoint {
+    timestamp: u64,
+    values: Vec<f64>,
+}
+
+impl DistributionPoint {
+    pub fn new(timestamp: u64, values: Vec<f64>) -> Self {
+        Self { timestamp, values }
+    }
+}
+
+impl Serialize for DistributionPoint {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut seq = serializer.serialize_seq(Some(2))?;
+        seq.serialize_element(&self.timestamp)?;
+        seq.serialize_element(&self.values)?;
+        seq.end()
+    }
+}
+
+/// # Examples
+///
+/// ```
+/// use datadog_client::metrics::{DistributionPoint, DistributionSerie};
+///
+/// let serie = DistributionSerie::new("request.duration")
+///     .set_host("raspberrypi")
+///     .add_point(DistributionPoint::new(123456, vec![12.34, 56.78]))
+///     .add_tag(String::from("whatever:tag"));
+/// ```
+#[derive(Debug, Clone, Serialize)]
+pub struct DistributionSerie {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    host: Option<String>,
+    metric: String,
+    points: Vec<DistributionPoint>,
+    tags: Vec<String>,
+    #[serde(rename = "type")]
+    dtype: Type,
+}
+
+impl DistributionSerie {
+    pub fn new(metric: &str) -> Self {
+        Self {
+            host: None,
+            metric: metric.to_string(),
+            points: Vec::new(),
+            tags: Vec::new(),
+            dtype: Type::Distribution,
+        }
+    }
+
+    pub fn set_host(mut self, host: &str) -> Self {
+        self.host = Some(host.to_string());
+        self
+    }
+
+    pub fn set_points(mut self, points: Vec<DistributionPoint>) -> Self {
+        self.points = points;
+        self
+    }
+
+    pub fn add_point(mut self, point: DistributionPoint) -> Self {
+        self.points.push(point);
+        self
+    }
+
+    pub fn set_tags(mut self, tags: Vec<String>) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    pub fn add_tag(mut self, tag: String) -> Self {
+        self.tags.push(tag);
+        self
+    }
+}
+
+impl Client {
+    /// Submit distribution points
+    ///
+    /// https://docs.datadoghq.com/api/latest/metrics/#submit-distribution-points
+    ///
+    pub async fn post_distribution_points(
+        &self,
+        series: &[DistributionSerie],
+    ) -> Result<(), Error> {
+        let payload = serde_json::json!({ "series": series });
+        self.post("/api/v1/distribution_points", &payload).await
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -155,7 +333,8 @@ mod tests {
         let client = Client::new(Config::new(
             mockito::server_url(),
             String::from("fake-api-key"),
-        ));
+        ))
+        .unwrap();
         let series = vec![Serie::new("something", Type::Gauge).add_point(Point::new(1234, 12.34))];
         let result = client.post_metrics(&series).await;
         assert!(result.is_ok());
@@ -171,10 +350,79 @@ mod tests {
         let client = Client::new(Config::new(
             mockito::server_url(),
             String::from("fake-api-key"),
-        ));
+        ))
+        .unwrap();
         let series = vec![Serie::new("something", Type::Gauge).add_point(Point::new(1234, 12.34))];
         let result = client.post_metrics(&series).await;
         assert!(result.is_err());
         call.expect(1);
     }
+
+    #[test]
+    fn validate_requires_interval_for_count_and_rate() {
+        let serie = Serie::new("metric", Type::Count)
+            .add_point(Point::new(crate::client::now_unix() as u64, 1.0));
+        let errors = serie.validate().unwrap_err();
+        assert_eq!(errors.len(), 1);
+        let serie = Serie::new("metric", Type::Rate)
+            .add_point(Point::new(crate::client::now_unix() as u64, 1.0));
+        let errors = serie.validate().unwrap_err();
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[test]
+    fn validate_rejects_a_point_too_far_in_the_past() {
+        let serie = Serie::new("metric", Type::Gauge).add_point(Point::new(0, 1.0));
+        let errors = serie.validate().unwrap_err();
+        assert_eq!(errors.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn post_metrics_short_circuits_when_validation_fails() {
+        let call = mock("POST", "/api/v1/series").with_status(202).create();
+        let client = Client::new(
+            Config::new(mockito::server_url(), String::from("fake-api-key"))
+                .set_validate_payloads(true),
+        )
+        .unwrap();
+        let series = vec![Serie::new("something", Type::Count).add_point(Point::new(0, 1.0))];
+        let result = client.post_metrics(&series).await;
+        assert!(matches!(result, Err(Error::Validation(_))));
+        call.expect(0);
+    }
+
+    #[test]
+    fn serialize_distribution_point() {
+        let point = DistributionPoint::new(1234, vec![1.0, 2.0]);
+        assert_eq!(serde_json::to_string(&point).unwrap(), "[1234,[1.0,2.0]]");
+    }
+
+    #[test]
+    fn serialize_distribution_serie() {
+        let serie = DistributionSerie::new("request.duration")
+            .add_point(DistributionPoint::new(1234, vec![1.0, 2.0]))
+            .add_tag(String::from("tag"))
+            .set_host("host");
+        assert_eq!(
+            serde_json::to_string(&serie).unwrap(),
+            "{\"host\":\"host\",\"metric\":\"request.duration\",\"points\":[[1234,[1.0,2.0]]],\"tags\":[\"tag\"],\"type\":\"distribution\"}"
+        );
+    }
+
+    #[tokio::test]
+    async fn post_distribution_points_success() {
+        let call = mock("POST", "/api/v1/distribution_points")
+            .with_status(202)
+            .create();
+        let client = Client::new(Config::new(
+            mockito::server_url(),
+            String::from("fake-api-key"),
+        ))
+        .unwrap();
+        let series = vec![DistributionSerie::new("request.duration")
+            .add_point(DistributionPoint::new(1234, vec![1.0, 2.0]))];
+        let result = client.post_distribution_points(&series).await;
+        assert!(result.is_ok());
+        call.expect(1);
+    }
 }